@@ -1,11 +1,21 @@
 use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use std::fmt;
 use std::io::{BufRead, BufReader, Read};
 
-const MAX_ROWS: usize = 9;
-const MAX_COLS: usize = 9;
-const MAX_SQUARES: usize = 9;
-const MAX_INDEX: usize = MAX_COLS * MAX_ROWS;
+mod solver;
+
+pub use solver::{AnnealingSolver, BacktrackSolver, PropagationSolver, SolveStep, Solver, Unit};
+
+/// Whether `n` is a perfect square, e.g. 4, 9 and 16 but not 10.
+const fn is_perfect_square(n: usize) -> bool {
+    let mut side = 0;
+    while side * side < n {
+        side += 1;
+    }
+    side * side == n
+}
 
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum Field {
@@ -19,58 +29,194 @@ impl fmt::Debug for Field {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         match self {
             Empty => write!(f, "*"),
-            Filled(value) => write!(f, "{}", value),
+            // Single token, matching what `parse_cell`/`parse_digit` accept:
+            // a decimal digit for 1-9, else an uppercase hex-style letter
+            // (`A`-`G` for 10-16). Plain `{:x}` would print 16 as the
+            // two-character `"10"`, which `read_from` then silently
+            // misparses as decimal 10 on the round trip.
+            Filled(value) if *value <= 9 => write!(f, "{}", value),
+            Filled(value) => write!(f, "{}", (b'A' + (value - 10)) as char),
         }
     }
 }
 
-pub struct Sudoku {
-    fields: [Field; MAX_INDEX],
+/// A Sudoku puzzle, either partial or complete, on an `N`x`N` grid of boxes
+/// that are `B`x`B` (`B` = sqrt(`N`)), e.g. `Sudoku<9>` for the usual 9x9
+/// grid of 3x3 boxes, or `Sudoku<4>`/`Sudoku<16>` for its smaller/larger
+/// cousins. `N` must be a perfect square (4, 9 and 16 are supported).
+pub struct Sudoku<const N: usize> {
+    pub(crate) fields: Vec<Field>,
+    /// For each cell, a bitset of the digits (1-N, stored as bits 0-(N-1))
+    /// that are still legal candidates for that cell. Always `0` for filled
+    /// cells.
+    pub(crate) candidates: Vec<u16>,
 }
 
-/// A Sudoku puzzle, either partial or complete
-impl Sudoku {
-    pub fn read_from<R: Read>(source: R) -> Option<Sudoku> {
+impl<const N: usize> Sudoku<N> {
+    pub fn read_from<R: Read>(source: R) -> Option<Sudoku<N>> {
         let source = BufReader::new(source);
-        let mut fields = [Empty; MAX_INDEX];
+        let mut fields = vec![Empty; N * N];
         let mut index = 0;
 
         for line in source.lines() {
-            for square in line.ok()?.split_whitespace() {
-                if square.len() != 1 {
-                    return None;
-                }
-                let square = square.chars().next()?;
-
-                if square.is_digit(10) {
-                    let digit = square.to_digit(10)?;
-                    if digit >= 1 && digit <= 9 {
-                        fields[index] = Filled(digit as u8);
-                    } else {
-                        return None;
-                    }
-                } else if square == '*' {
-                    fields[index] = Empty
-                } else {
-                    return None;
-                }
+            for token in line.ok()?.split_whitespace() {
+                let slot = fields.get_mut(index)?;
+                *slot = Self::parse_cell(token)?;
+                index += 1;
+            }
+        }
+
+        let candidates = Self::init_candidates(&fields);
 
+        Some(Sudoku { fields, candidates })
+    }
+
+    /// Parses the dense encodings: one character per cell with no
+    /// separators, `0` or `.` for empty. Covers both the single
+    /// `N`*`N`-character line popular in public puzzle datasets and the
+    /// equivalent dotted grid spread over `N` lines of `N` characters each;
+    /// whitespace between characters (including line breaks) is ignored.
+    pub fn read_from_dense<R: Read>(source: R) -> Option<Sudoku<N>> {
+        let source = BufReader::new(source);
+        let mut fields = vec![Empty; N * N];
+        let mut index = 0;
+
+        for line in source.lines() {
+            for ch in line.ok()?.chars().filter(|c| !c.is_whitespace()) {
+                let slot = fields.get_mut(index)?;
+                *slot = Self::parse_cell(&ch.to_string())?;
                 index += 1;
             }
         }
 
-        Some(Sudoku { fields })
+        if index != N * N {
+            return None;
+        }
+
+        let candidates = Self::init_candidates(&fields);
+
+        Some(Sudoku { fields, candidates })
+    }
+
+    /// Parses one token: `*`, `0` or `.` for empty, otherwise a digit from 1
+    /// to `N`. Tokens are already split on whitespace (or, for the dense
+    /// encodings, one token per character), so larger boards (e.g. 16x16)
+    /// can use either a decimal number (`"10"`, `"16"`) or a single
+    /// hex-style letter (`A`-`G` for 10-16).
+    fn parse_cell(token: &str) -> Option<Field> {
+        if token == "*" || token == "0" || token == "." {
+            return Some(Empty);
+        }
+
+        let value = Self::parse_digit(token)?;
+        if value >= 1 && value as usize <= N {
+            Some(Filled(value))
+        } else {
+            None
+        }
+    }
+
+    fn parse_digit(token: &str) -> Option<u8> {
+        if let Ok(value) = token.parse::<u8>() {
+            return Some(value);
+        }
+
+        if token.len() == 1 {
+            let letter = token.chars().next()?.to_ascii_uppercase();
+            if letter.is_ascii_alphabetic() {
+                return Some(10 + (letter as u8 - b'A'));
+            }
+        }
+
+        None
     }
 
-    fn set_field(&mut self, index: usize, value: u8) {
+    /// Compile-time guard that `N` is a perfect square (4, 9, 16, ...), so
+    /// `box_side` and everything built on it (peers, boxes, `read_from`'s
+    /// hex digits) actually tile the grid instead of silently misbehaving.
+    /// Evaluated the first time any box-aware method on `Sudoku<N>` is
+    /// monomorphized, turning an unsupported `N` into a compile error.
+    const ASSERT_N_IS_PERFECT_SQUARE: () = assert!(
+        is_perfect_square(N),
+        "Sudoku::<N>: N must be a perfect square (e.g. 4, 9, 16)"
+    );
+
+    /// The side length of a box, e.g. 3 for a 9x9 grid. `N` is required to
+    /// be a perfect square, so this is exact.
+    fn box_side() -> usize {
+        let () = Self::ASSERT_N_IS_PERFECT_SQUARE;
+
+        let mut side = 1;
+        while (side + 1) * (side + 1) <= N {
+            side += 1;
+        }
+        side
+    }
+
+    /// Bitset with all `N` candidate digits set.
+    fn full_candidates() -> u16 {
+        ((1u32 << N) - 1) as u16
+    }
+
+    /// Builds the initial candidate masks for a freshly parsed board: every
+    /// empty cell starts with all `N` digits possible, then each given
+    /// clears its digit from the masks of its row/column/square peers.
+    fn init_candidates(fields: &[Field]) -> Vec<u16> {
+        let mut candidates = vec![Self::full_candidates(); N * N];
+
+        for (index, field) in fields.iter().enumerate() {
+            if *field != Empty {
+                candidates[index] = 0;
+            }
+        }
+
+        for (index, field) in fields.iter().enumerate() {
+            if let Filled(value) = field {
+                for peer in Self::peers_of(index) {
+                    candidates[peer] &= !(1 << (value - 1));
+                }
+            }
+        }
+
+        candidates
+    }
+
+    /// Indices of every other cell that shares a row, column or square with
+    /// `index`, i.e. every cell whose value constrains `index`'s candidates.
+    pub(crate) fn peers_of(index: usize) -> impl Iterator<Item = usize> {
+        let side = Self::box_side();
+        let row = index / N;
+        let col = index % N;
+        let square_col = (col / side) * side;
+        let square_row = (row / side) * side;
+
+        let row_peers = (row * N)..(row * N + N);
+        let col_peers = (0..N).map(move |r| col + r * N);
+        let square_peers =
+            (0..N).map(move |i| (square_col + i % side) + N * (square_row + i / side));
+
+        row_peers
+            .chain(col_peers)
+            .chain(square_peers)
+            .filter(move |&peer| peer != index)
+    }
+
+    pub(crate) fn set_field(&mut self, index: usize, value: u8) {
         self.fields[index] = Filled(value);
+        self.candidates[index] = 0;
+
+        for peer in Self::peers_of(index) {
+            self.candidates[peer] &= !(1 << (value - 1));
+        }
     }
 
     pub fn into_iter(self) -> impl Iterator<Item = Field> {
         self.fields.into_iter()
     }
 
-    fn get_first_empty_index(&self) -> Option<usize> {
+    /// The first empty cell in reading order, or `None` once the board is
+    /// full.
+    pub(crate) fn first_empty_index(&self) -> Option<usize> {
         self.fields
             .iter()
             .enumerate()
@@ -78,64 +224,119 @@ impl Sudoku {
             .map(|(index, _field)| index)
     }
 
-    fn get_possible_values(&self, index: usize) -> Vec<u8> {
-        let possible_values = [1, 2, 3, 4, 5, 6, 7, 8, 9];
-        let row = self.row_of(index);
-        let col = self.col_of(index);
-        let square = self.square_of(index);
-
-        possible_values
-            .into_iter()
-            .filter(|value| {
-                let not_in_row = !row.into_iter().contains(&Filled(*value));
-                let not_in_col = !col.into_iter().contains(&Filled(*value));
-                let not_in_square = !square.into_iter().contains(&Filled(*value));
-
-                not_in_row && not_in_col && not_in_square
-            })
-            .collect()
+    /// The empty cell with the fewest remaining candidates (the
+    /// minimum-remaining-value heuristic), or `None` once the board is full.
+    pub(crate) fn get_min_candidate_index(&self) -> Option<usize> {
+        self.fields
+            .iter()
+            .enumerate()
+            .filter(|(_index, &field)| field == Empty)
+            .min_by_key(|(index, _field)| self.candidates[*index].count_ones())
+            .map(|(index, _field)| index)
     }
 
-    pub fn solve(self) -> Sudoku {
-        Self::solve_impl(self).unwrap()
+    /// Repeatedly applies naked-single and hidden-single deductions until
+    /// neither rule makes progress. Returns `false` if the board was proven
+    /// unsolvable along the way (an empty cell ran out of candidates).
+    pub(crate) fn propagate(&mut self) -> bool {
+        self.propagate_logged(&mut |_step| {})
     }
 
-    fn solve_impl(puzzle: Sudoku) -> Option<Sudoku> {
-        let index = puzzle.get_first_empty_index();
+    /// Same deductions as `propagate`, reporting each one through `on_step`
+    /// as it's applied so callers can build a human-readable solve log.
+    pub(crate) fn propagate_logged(&mut self, on_step: &mut dyn FnMut(SolveStep)) -> bool {
+        loop {
+            let mut changed = false;
+
+            for index in 0..self.fields.len() {
+                if self.fields[index] != Empty {
+                    continue;
+                }
+
+                let candidates = self.candidates[index];
+                if candidates == 0 {
+                    return false;
+                }
 
-        match index {
-            None => {
-                if puzzle.is_valid() {
-                    Some(puzzle)
-                } else {
-                    None
+                if candidates.count_ones() == 1 {
+                    let value = candidates.trailing_zeros() as u8 + 1;
+                    self.set_field(index, value);
+                    on_step(SolveStep::NakedSingle { index, value });
+                    changed = true;
                 }
             }
-            Some(index) => {
-                let possible_values = puzzle.get_possible_values(index);
-                possible_values
-                    .into_iter()
-                    .fold(None, |prev_result, value| {
-                        if prev_result.is_some() {
-                            return prev_result;
-                        }
-
-                        let mut puzzle = puzzle.clone();
-                        puzzle.set_field(index, value);
-                        if let Some(answer) = Self::solve_impl(puzzle) {
-                            return Some(answer);
-                        } else {
-                            None
-                        }
-                    })
+
+            if self.apply_hidden_singles(on_step) {
+                changed = true;
+            }
+
+            if !changed {
+                return true;
             }
         }
     }
 
-    fn is_valid(&self) -> bool {
+    /// A single pass of the hidden-single rule: within any row, column or
+    /// square, if a digit is a candidate of exactly one empty cell, that
+    /// cell must hold it. Returns whether any cell was filled.
+    fn apply_hidden_singles(&mut self, on_step: &mut dyn FnMut(SolveStep)) -> bool {
+        let mut changed = false;
+
+        for (unit, indices) in Self::units() {
+            for value in 1..=N as u8 {
+                let bit = 1 << (value - 1);
+                let mut candidate_cells = indices.iter().copied().filter(|&index| {
+                    self.fields[index] == Empty && self.candidates[index] & bit != 0
+                });
+
+                if let Some(index) = candidate_cells.next() {
+                    if candidate_cells.next().is_none() {
+                        self.set_field(index, value);
+                        on_step(SolveStep::HiddenSingle { index, value, unit });
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+
+    /// The index groups (rows, then columns, then squares) that each must
+    /// contain every digit exactly once, tagged with the `Unit` they are so
+    /// deductions within them can be named (e.g. "hidden single in box 5").
+    fn units() -> impl Iterator<Item = (Unit, Vec<usize>)> {
+        (0..N)
+            .map(|i| (Unit::Row(i), Self::row_indices(i)))
+            .chain((0..N).map(|i| (Unit::Column(i), Self::col_indices(i))))
+            .chain((0..N).map(|i| (Unit::Square(i), Self::square_indices(i))))
+    }
+
+    /// The `N` cell indices, in reading order, making up `row`.
+    pub(crate) fn row_indices(row: usize) -> Vec<usize> {
+        (0..N).map(|i| row * N + i).collect()
+    }
+
+    /// The `N` cell indices, in reading order, making up `col`.
+    pub(crate) fn col_indices(col: usize) -> Vec<usize> {
+        (0..N).map(|i| col + i * N).collect()
+    }
+
+    /// The `N` cell indices, in reading order, making up `square` (numbered
+    /// left-to-right, top-to-bottom, same as `square_of`).
+    pub(crate) fn square_indices(square: usize) -> Vec<usize> {
+        let side = Self::box_side();
+        let square_col = (square % side) * side;
+        let square_row = (square / side) * side;
+        (0..N)
+            .map(|i| (square_col + i % side) + N * (square_row + i / side))
+            .collect()
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
         for row in self.rows() {
             let is_filled = !row.iter().contains(&Empty);
-            let is_unique = row.iter().unique().count() == 9;
+            let is_unique = row.iter().unique().count() == N;
 
             if !is_filled || !is_unique {
                 return false;
@@ -144,7 +345,7 @@ impl Sudoku {
 
         for col in self.cols() {
             let is_filled = !col.into_iter().contains(&Empty);
-            let is_unique = col.into_iter().unique().count() == 9;
+            let is_unique = col.into_iter().unique().count() == N;
 
             if !is_filled || !is_unique {
                 return false;
@@ -153,7 +354,7 @@ impl Sudoku {
 
         for square in self.squares() {
             let is_filled = !square.into_iter().contains(&Empty);
-            let is_unique = square.into_iter().unique().count() == 9;
+            let is_unique = square.into_iter().unique().count() == N;
 
             if !is_filled || !is_unique {
                 return false;
@@ -163,64 +364,143 @@ impl Sudoku {
         true
     }
 
-    fn rows(&self) -> Rows {
+    fn rows(&self) -> Rows<N> {
         Rows {
             puzzle: self,
             curr_index: 0,
         }
     }
 
-    fn row_of(&self, index: usize) -> <Rows as Iterator>::Item {
-        let row_index = index / MAX_COLS;
+    #[cfg(test)]
+    fn row_of(&self, index: usize) -> <Rows<N> as Iterator>::Item {
+        let row_index = index / N;
         self.rows().nth(row_index).unwrap()
     }
 
-    fn cols(&self) -> Cols {
+    fn cols(&self) -> Cols<N> {
         Cols {
             puzzle: self,
             curr_col: 0,
         }
     }
 
-    fn col_of(&self, index: usize) -> <Cols as Iterator>::Item {
-        let col_index = index % MAX_ROWS;
+    #[cfg(test)]
+    fn col_of(&self, index: usize) -> <Cols<N> as Iterator>::Item {
+        let col_index = index % N;
         self.cols().nth(col_index).unwrap()
     }
 
-    fn squares(&self) -> Squares {
+    fn squares(&self) -> Squares<N> {
         Squares {
             puzzle: self,
             curr_square: 0,
         }
     }
 
-    fn square_of(&self, index: usize) -> <Squares as Iterator>::Item {
-        let col_index = (index % 9) / 3;
-        let row_index = (index / 9) / 3;
-        let square_index = col_index + 3 * row_index;
+    #[cfg(test)]
+    fn square_of(&self, index: usize) -> <Squares<N> as Iterator>::Item {
+        let side = Self::box_side();
+        let col_index = (index % N) / side;
+        let row_index = (index / N) / side;
+        let square_index = col_index + side * row_index;
         self.squares().nth(square_index).unwrap()
     }
+
+    /// Generates a new puzzle with exactly `clues` givens (or more, if no
+    /// further removal keeps the solution unique): fills an empty board to a
+    /// random full solution, then removes givens at random one at a time,
+    /// keeping each removal only while the board still has a single
+    /// solution.
+    pub fn generate(clues: usize) -> Sudoku<N> {
+        let mut rng = rand::thread_rng();
+        let solved = Self::random_solved_board(&mut rng);
+        Self::remove_givens(solved, clues, &mut rng)
+    }
+
+    /// Fills an empty board to a full, valid solution by recursing on the
+    /// most-constrained cell with its candidates tried in random order.
+    fn random_solved_board(rng: &mut impl Rng) -> Sudoku<N> {
+        let empty = Sudoku {
+            fields: vec![Empty; N * N],
+            candidates: vec![Self::full_candidates(); N * N],
+        };
+
+        Self::fill_randomly(empty, rng).expect("an empty board always has a solution")
+    }
+
+    fn fill_randomly(mut puzzle: Sudoku<N>, rng: &mut impl Rng) -> Option<Sudoku<N>> {
+        if !puzzle.propagate() {
+            return None;
+        }
+
+        match puzzle.get_min_candidate_index() {
+            None => Some(puzzle),
+            Some(index) => {
+                let candidates = puzzle.candidates[index];
+                let mut values: Vec<u8> = (1..=N as u8)
+                    .filter(|value| candidates & (1 << (value - 1)) != 0)
+                    .collect();
+                values.shuffle(rng);
+
+                values.into_iter().find_map(|value| {
+                    let mut attempt = puzzle.clone();
+                    attempt.set_field(index, value);
+                    Self::fill_randomly(attempt, rng)
+                })
+            }
+        }
+    }
+
+    /// Removes givens from `puzzle` in random order, keeping each removal
+    /// only while the board still has exactly one solution, until either
+    /// `clues` remain or no further cell can be removed.
+    fn remove_givens(mut puzzle: Sudoku<N>, clues: usize, rng: &mut impl Rng) -> Sudoku<N> {
+        let mut indices: Vec<usize> = (0..N * N).collect();
+        indices.shuffle(rng);
+
+        let mut remaining = N * N;
+
+        for index in indices {
+            if remaining <= clues {
+                break;
+            }
+
+            let removed = puzzle.fields[index];
+            puzzle.fields[index] = Empty;
+            puzzle.candidates = Self::init_candidates(&puzzle.fields);
+
+            if solver::PropagationSolver.count_solutions(&puzzle, 2) == 1 {
+                remaining -= 1;
+            } else {
+                puzzle.fields[index] = removed;
+                puzzle.candidates = Self::init_candidates(&puzzle.fields);
+            }
+        }
+
+        puzzle
+    }
 }
 
-impl Clone for Sudoku {
+impl<const N: usize> Clone for Sudoku<N> {
     fn clone(&self) -> Self {
         Self {
             fields: self.fields.clone(),
+            candidates: self.candidates.clone(),
         }
     }
 }
 
 // impl Copy for Sudoku {}
 
-impl PartialEq for Sudoku {
+impl<const N: usize> PartialEq for Sudoku<N> {
     fn eq(&self, other: &Self) -> bool {
         self.fields == other.fields
     }
 }
 
-impl Eq for Sudoku {}
+impl<const N: usize> Eq for Sudoku<N> {}
 
-impl fmt::Debug for Sudoku {
+impl<const N: usize> fmt::Debug for Sudoku<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         for row in self.rows() {
             for element in row {
@@ -232,33 +512,33 @@ impl fmt::Debug for Sudoku {
     }
 }
 
-struct Rows<'a> {
-    puzzle: &'a Sudoku,
+struct Rows<'a, const N: usize> {
+    puzzle: &'a Sudoku<N>,
     curr_index: usize,
 }
 
-impl<'a> Iterator for Rows<'a> {
+impl<'a, const N: usize> Iterator for Rows<'a, N> {
     type Item = &'a [Field];
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.curr_index >= MAX_INDEX {
+        if self.curr_index >= N * N {
             None
         } else {
-            let result = &self.puzzle.fields[self.curr_index..self.curr_index + MAX_COLS];
-            self.curr_index += MAX_COLS;
+            let result = &self.puzzle.fields[self.curr_index..self.curr_index + N];
+            self.curr_index += N;
             Some(result)
         }
     }
 }
 
-struct Col<'a> {
-    puzzle: &'a Sudoku,
+struct Col<'a, const N: usize> {
+    puzzle: &'a Sudoku<N>,
     start_index: usize,
 }
 
-impl<'a> IntoIterator for &Col<'a> {
+impl<'a, const N: usize> IntoIterator for &Col<'a, N> {
     type Item = Field;
-    type IntoIter = ColIter<'a>;
+    type IntoIter = ColIter<'a, N>;
 
     fn into_iter(self) -> Self::IntoIter {
         ColIter {
@@ -268,35 +548,35 @@ impl<'a> IntoIterator for &Col<'a> {
     }
 }
 
-struct ColIter<'a> {
-    puzzle: &'a Sudoku,
+struct ColIter<'a, const N: usize> {
+    puzzle: &'a Sudoku<N>,
     curr_index: usize,
 }
 
-impl<'a> Iterator for ColIter<'a> {
+impl<'a, const N: usize> Iterator for ColIter<'a, N> {
     type Item = Field;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.curr_index >= MAX_INDEX {
+        if self.curr_index >= N * N {
             None
         } else {
             let result = self.puzzle.fields[self.curr_index];
-            self.curr_index += MAX_ROWS;
+            self.curr_index += N;
             Some(result)
         }
     }
 }
 
-struct Cols<'a> {
-    puzzle: &'a Sudoku,
+struct Cols<'a, const N: usize> {
+    puzzle: &'a Sudoku<N>,
     curr_col: usize,
 }
 
-impl<'a> Iterator for Cols<'a> {
-    type Item = Col<'a>;
+impl<'a, const N: usize> Iterator for Cols<'a, N> {
+    type Item = Col<'a, N>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.curr_col >= MAX_COLS {
+        if self.curr_col >= N {
             None
         } else {
             let result = Col {
@@ -309,14 +589,14 @@ impl<'a> Iterator for Cols<'a> {
     }
 }
 
-struct Square<'a> {
-    puzzle: &'a Sudoku,
+struct Square<'a, const N: usize> {
+    puzzle: &'a Sudoku<N>,
     square: usize,
 }
 
-impl<'a> IntoIterator for &Square<'a> {
+impl<'a, const N: usize> IntoIterator for &Square<'a, N> {
     type Item = Field;
-    type IntoIter = SquareIter<'a>;
+    type IntoIter = SquareIter<'a, N>;
 
     fn into_iter(self) -> Self::IntoIter {
         SquareIter {
@@ -327,26 +607,27 @@ impl<'a> IntoIterator for &Square<'a> {
     }
 }
 
-struct SquareIter<'a> {
-    puzzle: &'a Sudoku,
+struct SquareIter<'a, const N: usize> {
+    puzzle: &'a Sudoku<N>,
     square: usize,
     curr_index: usize,
 }
 
-impl<'a> Iterator for SquareIter<'a> {
+impl<'a, const N: usize> Iterator for SquareIter<'a, N> {
     type Item = Field;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.curr_index >= MAX_SQUARES {
+        if self.curr_index >= N {
             None
         } else {
-            let square_col_index = self.curr_index % 3;
-            let square_row_index = self.curr_index / 3;
+            let side = Sudoku::<N>::box_side();
+            let square_col_index = self.curr_index % side;
+            let square_row_index = self.curr_index / side;
 
-            let col_index = square_col_index + 3 * (self.square % 3);
-            let row_index = square_row_index + 3 * (self.square / 3);
+            let col_index = square_col_index + side * (self.square % side);
+            let row_index = square_row_index + side * (self.square / side);
 
-            let index = col_index + MAX_COLS * row_index;
+            let index = col_index + N * row_index;
             let result = self.puzzle.fields[index];
             self.curr_index += 1;
             Some(result)
@@ -354,16 +635,16 @@ impl<'a> Iterator for SquareIter<'a> {
     }
 }
 
-struct Squares<'a> {
-    puzzle: &'a Sudoku,
+struct Squares<'a, const N: usize> {
+    puzzle: &'a Sudoku<N>,
     curr_square: usize,
 }
 
-impl<'a> Iterator for Squares<'a> {
-    type Item = Square<'a>;
+impl<'a, const N: usize> Iterator for Squares<'a, N> {
+    type Item = Square<'a, N>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.curr_square >= MAX_SQUARES {
+        if self.curr_square >= N {
             None
         } else {
             let result = Square {
@@ -378,7 +659,7 @@ impl<'a> Iterator for Squares<'a> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Empty, Field, Sudoku};
+    use crate::{Empty, Field, PropagationSolver, Solver, Sudoku};
 
     #[test]
     fn empty_puzzle() {
@@ -392,7 +673,7 @@ mod tests {
             + "* * * * * * * * *\n"
             + "* * * * * * * * *\n";
 
-        let puzzle = Sudoku::read_from(str_puzzle.as_bytes()).unwrap();
+        let puzzle = Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap();
 
         for field in puzzle.into_iter() {
             assert_eq!(field, Empty);
@@ -411,7 +692,7 @@ mod tests {
             + "1 2 3 4 5 6 7 8 9\n"
             + "1 2 3 4 5 6 7 8 9\n";
 
-        let puzzle = Sudoku::read_from(str_puzzle.as_bytes()).unwrap();
+        let puzzle = Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap();
         let mut expectation = 1;
 
         for field in puzzle.into_iter() {
@@ -436,7 +717,7 @@ mod tests {
             + "1 2 3 4 5 6 7 8 9\n"
             + "1 2 3 4 5 6 7 8 9\n";
 
-        assert!(Sudoku::read_from(str_puzzle.as_bytes()).is_none());
+        assert!(Sudoku::<9>::read_from(str_puzzle.as_bytes()).is_none());
 
         let str_puzzle = "1 2 3 4 5 6 7 8 9\n".to_owned()
             + "1 2 3 4 5 6 L 8 9\n"
@@ -448,7 +729,7 @@ mod tests {
             + "1 2 3 4 5 6 7 8 9\n"
             + "1 2 3 4 5 6 7 8 9\n";
 
-        assert!(Sudoku::read_from(str_puzzle.as_bytes()).is_none());
+        assert!(Sudoku::<9>::read_from(str_puzzle.as_bytes()).is_none());
 
         let str_puzzle = "1 2 3 4 5 6 7 8 9\n".to_owned()
             + "1 2 3 4 5 6 7 8 9\n"
@@ -460,7 +741,7 @@ mod tests {
             + "1 2 3 4 5 6 7 8 9\n"
             + "1 2 3 4 5 6 7 8 9\n";
 
-        assert!(Sudoku::read_from(str_puzzle.as_bytes()).is_none());
+        assert!(Sudoku::<9>::read_from(str_puzzle.as_bytes()).is_none());
     }
 
     #[test]
@@ -475,7 +756,7 @@ mod tests {
             + "8 9 1 2 3 4 5 6 7\n"
             + "9 1 2 3 4 5 6 7 8\n";
 
-        let puzzle = Sudoku::read_from(str_puzzle.as_bytes()).unwrap();
+        let puzzle = Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap();
 
         for (starting_index, row) in puzzle.rows().enumerate() {
             let mut expectations = [1, 2, 3, 4, 5, 6, 7, 8, 9].map(Field::Filled);
@@ -497,7 +778,7 @@ mod tests {
             + "8 9 1 2 3 4 5 6 7\n"
             + "9 1 2 3 4 5 6 7 8\n";
 
-        let puzzle = Sudoku::read_from(str_puzzle.as_bytes()).unwrap();
+        let puzzle = Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap();
 
         for row_index in 0..9 {
             for col_index in 0..9 {
@@ -524,7 +805,7 @@ mod tests {
             + "8 9 1 2 3 4 5 6 7\n"
             + "9 1 2 3 4 5 6 7 8\n";
 
-        let puzzle = Sudoku::read_from(str_puzzle.as_bytes()).unwrap();
+        let puzzle = Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap();
 
         for (starting_index, col) in puzzle.cols().enumerate() {
             let mut expectations = [1, 2, 3, 4, 5, 6, 7, 8, 9].map(Field::Filled);
@@ -546,7 +827,7 @@ mod tests {
             + "8 9 1 2 3 4 5 6 7\n"
             + "9 1 2 3 4 5 6 7 8\n";
 
-        let puzzle = Sudoku::read_from(str_puzzle.as_bytes()).unwrap();
+        let puzzle = Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap();
 
         for row_index in 0..9 {
             for col_index in 0..9 {
@@ -573,7 +854,7 @@ mod tests {
             + "1 2 3 2 3 4 3 4 5\n"
             + "4 5 6 5 6 7 6 7 8\n";
 
-        let puzzle = Sudoku::read_from(str_puzzle.as_bytes()).unwrap();
+        let puzzle = Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap();
 
         for (starting_index, square) in puzzle.squares().enumerate() {
             let mut expectations = [1, 2, 3, 4, 5, 6, 7, 8, 9].map(Field::Filled);
@@ -595,7 +876,7 @@ mod tests {
             + "1 2 3 2 3 4 3 4 5\n"
             + "4 5 6 5 6 7 6 7 8\n";
 
-        let puzzle = Sudoku::read_from(str_puzzle.as_bytes()).unwrap();
+        let puzzle = Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap();
 
         for row_index in 0..9 {
             for col_index in 0..9 {
@@ -623,38 +904,168 @@ mod tests {
             + "6 7 8 9 1 2 3 4 5\n"
             + "9 1 2 3 4 5 6 7 8\n";
 
-        let puzzle = Sudoku::read_from(str_puzzle.as_bytes()).unwrap();
+        let puzzle = Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap();
 
         assert!(puzzle.is_valid());
     }
 
     #[test]
-    fn easy_solve() {
-        let str_puzzle = "* 8 6 * 4 1 * 3 9\n".to_owned()
-            + "* 4 * * * 7 8 * *\n"
-            + "* * 9 * * 6 2 4 *\n"
-            + "7 3 * * * 4 6 * *\n"
-            + "1 * * 2 * * * 9 5\n"
-            + "* * * 6 5 * * 7 4\n"
-            + "* * 2 * 6 9 5 * 3\n"
-            + "8 * * 3 1 * * * 2\n"
-            + "6 5 3 * * * 9 * *\n";
-
-        let mut puzzle = Sudoku::read_from(str_puzzle.as_bytes()).unwrap();
-
-        puzzle.solve();
-
-        let str_answer = "2 8 6 5 4 1 7 3 9\n".to_owned()
-            + "3 4 1 9 2 7 8 5 6\n"
-            + "5 7 9 8 3 6 2 4 1\n"
-            + "7 3 5 1 9 4 6 2 8\n"
-            + "1 6 4 2 7 8 3 9 5\n"
-            + "9 2 8 6 5 3 1 7 4\n"
-            + "4 1 2 7 6 9 5 8 3\n"
-            + "8 9 7 3 1 5 4 6 2\n"
-            + "6 5 3 4 8 2 9 1 7\n";
-        let answer = Sudoku::read_from(str_answer.as_bytes()).unwrap();
-
-        assert_eq!(puzzle, answer);
+    fn naked_single_propagation() {
+        let str_puzzle = "1 2 3 4 5 6 7 8 *\n".to_owned()
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n";
+
+        let mut puzzle = Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap();
+
+        assert_eq!(puzzle.candidates[8], 1 << 8);
+
+        assert!(puzzle.propagate());
+
+        assert_eq!(puzzle.fields[8], Field::Filled(9));
+    }
+
+    #[test]
+    fn hidden_single_propagation() {
+        // Square 0 is missing {7, 8, 9} across three empty cells (indices 2,
+        // 11 and 19), but a 9 placed elsewhere in column 2 rules it out of
+        // cells 2 and 11. Only cell 19 can still take a 9, even though it
+        // has three raw candidates left (so it isn't a naked single).
+        let str_puzzle = "1 2 * * * * * * *\n".to_owned()
+            + "3 4 * * * * * * *\n"
+            + "5 * 6 * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * 9 * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n";
+
+        let mut puzzle = Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap();
+
+        assert_eq!(puzzle.candidates[19], 0b1_1100_0000);
+
+        assert!(puzzle.propagate());
+
+        assert_eq!(puzzle.fields[19], Field::Filled(9));
+    }
+
+    #[test]
+    fn propagate_detects_contradiction() {
+        let str_puzzle = "1 2 3 4 5 6 7 8 *\n".to_owned()
+            + "* * * * * * * * 9\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n";
+
+        let mut puzzle = Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap();
+
+        assert!(!puzzle.propagate());
+    }
+
+    #[test]
+    fn dense_single_line_parse() {
+        let str_puzzle =
+            "123456789123456789123456789123456789123456789123456789123456789123456789123456789";
+
+        let puzzle = Sudoku::<9>::read_from_dense(str_puzzle.as_bytes()).unwrap();
+        let mut expectation = 1;
+
+        for field in puzzle.into_iter() {
+            assert_eq!(field, Field::Filled(expectation));
+
+            expectation += 1;
+            if expectation > 9 {
+                expectation = 1;
+            }
+        }
+    }
+
+    #[test]
+    fn dense_dotted_grid_parse() {
+        let str_puzzle = "1........\n".to_owned()
+            + ".2.......\n"
+            + "..3......\n"
+            + "...4.....\n"
+            + "....5....\n"
+            + ".....6...\n"
+            + "......7..\n"
+            + ".......8.\n"
+            + "........9\n";
+
+        let puzzle = Sudoku::<9>::read_from_dense(str_puzzle.as_bytes()).unwrap();
+
+        for (index, field) in puzzle.into_iter().enumerate() {
+            let expectation = if index % 10 == 0 {
+                Field::Filled((index / 10 + 1) as u8)
+            } else {
+                Empty
+            };
+            assert_eq!(field, expectation);
+        }
+    }
+
+    #[test]
+    fn dense_parse_rejects_wrong_length() {
+        assert!(Sudoku::<9>::read_from_dense("123".as_bytes()).is_none());
+    }
+
+    #[test]
+    fn four_by_four_parse_and_validity() {
+        let str_puzzle = "1 2 3 4\n".to_owned() + "3 4 1 2\n" + "2 1 4 3\n" + "4 3 2 1\n";
+
+        let puzzle = Sudoku::<4>::read_from(str_puzzle.as_bytes()).unwrap();
+
+        assert!(puzzle.is_valid());
+    }
+
+    #[test]
+    fn sixteen_by_sixteen_hex_parse() {
+        let str_puzzle = "1 2 3 4 5 6 7 8 9 A B C D E F G\n".to_owned()
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n"
+            + "* * * * * * * * * * * * * * * *\n";
+
+        let puzzle = Sudoku::<16>::read_from(str_puzzle.as_bytes()).unwrap();
+
+        let mut expectation = 1;
+        for field in puzzle.rows().next().unwrap() {
+            assert_eq!(*field, Field::Filled(expectation));
+            expectation += 1;
+        }
+    }
+
+    #[test]
+    fn generate_produces_a_uniquely_solvable_puzzle_with_the_requested_clues() {
+        let puzzle = Sudoku::<9>::generate(30);
+
+        let clues = puzzle
+            .fields
+            .iter()
+            .filter(|&&field| field != Empty)
+            .count();
+        assert_eq!(clues, 30);
+        assert_eq!(PropagationSolver.count_solutions(&puzzle, 2), 1);
     }
 }
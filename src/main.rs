@@ -1,16 +1,140 @@
+use std::fs::File;
 use std::io::{self, Write};
+use std::path::PathBuf;
 
 #[cfg(not(target_env = "msvc"))]
 use jemallocator::Jemalloc;
 
+use clap::{Parser, ValueEnum};
+
+use rusty_at_sudoku::{AnnealingSolver, BacktrackSolver, Field, PropagationSolver, Solver, Sudoku};
+
 #[cfg(not(target_env = "msvc"))]
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
+/// Solves a 9x9 Sudoku puzzle read from a file or stdin.
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Puzzle file to read; reads stdin if omitted.
+    input: Option<PathBuf>,
+
+    /// Where to write the solution; writes stdout if omitted.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Encoding of the input puzzle.
+    #[arg(short, long, value_enum, default_value_t = Format::Whitespace)]
+    format: Format,
+
+    /// Solver backend to use. `annealing` is stochastic: giving up is not
+    /// proof the puzzle is unsolvable, and it can take seconds to give up
+    /// on one that genuinely isn't.
+    #[arg(short, long, value_enum, default_value_t = Backend::Propagation)]
+    solver: Backend,
+
+    /// Print a single dense 81-character line instead of the debug grid.
+    #[arg(short, long)]
+    compact: bool,
+
+    /// Print the step-by-step deduction log before the solution. Always
+    /// uses the propagation solver, regardless of `--solver`.
+    #[arg(short, long)]
+    log: bool,
+}
+
+/// The puzzle encodings this CLI understands, backed by
+/// `Sudoku::read_from`/`Sudoku::read_from_dense`.
+#[derive(Copy, Clone, ValueEnum)]
+enum Format {
+    /// The crate's original whitespace-separated tokens, `*` for empty.
+    Whitespace,
+    /// One character per cell with no separators: a single 81-character
+    /// line, or the equivalent dotted grid, `0`/`.` for empty.
+    Dense,
+}
+
+/// The available `Solver` backends.
+#[derive(Copy, Clone, ValueEnum)]
+enum Backend {
+    Backtrack,
+    Propagation,
+    /// Stochastic; see the `--solver` help and `AnnealingSolver`'s doc
+    /// comment for why giving up doesn't mean the puzzle is unsolvable.
+    Annealing,
+}
+
+impl Backend {
+    fn solver(self) -> Box<dyn Solver<9>> {
+        match self {
+            Backend::Backtrack => Box::new(BacktrackSolver),
+            Backend::Propagation => Box::new(PropagationSolver),
+            Backend::Annealing => Box::new(AnnealingSolver),
+        }
+    }
+}
+
 fn main() {
-    let stdin = io::stdin();
-    let stdout = io::stdout();
+    let cli = Cli::parse();
+
+    let puzzle = match &cli.input {
+        Some(path) => {
+            let file = File::open(path).expect("failed to open input file");
+            read_puzzle(file, cli.format)
+        }
+        None => read_puzzle(io::stdin(), cli.format),
+    }
+    .expect("failed to parse puzzle");
+
+    let mut output: Box<dyn Write> = match &cli.output {
+        Some(path) => Box::new(File::create(path).expect("failed to create output file")),
+        None => Box::new(io::stdout()),
+    };
+
+    let solution = if cli.log {
+        let (solution, log) = PropagationSolver
+            .solve_with_log(&puzzle)
+            .expect("puzzle has no solution");
+
+        for step in &log {
+            writeln!(output, "{}", step.describe::<9>()).unwrap();
+        }
+
+        solution
+    } else {
+        let failure_message = match cli.solver {
+            Backend::Annealing => {
+                "annealing gave up without converging (not proof the puzzle has no solution)"
+            }
+            Backend::Backtrack | Backend::Propagation => "puzzle has no solution",
+        };
+
+        cli.solver.solver().solve(&puzzle).expect(failure_message)
+    };
+
+    if cli.compact {
+        writeln!(output, "{}", format_dense(solution)).unwrap();
+    } else {
+        write!(output, "{:?}", &solution).unwrap();
+    }
+}
+
+fn read_puzzle<R: io::Read>(source: R, format: Format) -> Option<Sudoku<9>> {
+    match format {
+        Format::Whitespace => Sudoku::read_from(source),
+        Format::Dense => Sudoku::read_from_dense(source),
+    }
+}
 
-    let puzzle = rusty_at_sudoku::Sudoku::read_from(stdin).unwrap().solve();
-    write!(stdout.lock(), "{:?}\n", &puzzle).unwrap();
+/// Renders a fully solved puzzle as a single 81-character line, `0` for
+/// empty, the inverse of `Format::Dense` parsing.
+fn format_dense(puzzle: Sudoku<9>) -> String {
+    puzzle
+        .into_iter()
+        .map(|field| match field {
+            Field::Empty => '0',
+            Field::Filled(value) => (b'0' + value) as char,
+        })
+        .collect()
 }
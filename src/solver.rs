@@ -0,0 +1,581 @@
+use crate::{Field, Sudoku};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+
+/// A pluggable Sudoku-solving strategy for an `N`x`N` board.
+///
+/// Implementations are stateless and take the puzzle by shared reference so
+/// they stay directly comparable and benchmarkable against one another.
+pub trait Solver<const N: usize> {
+    /// Attempts to solve `puzzle`, returning the completed board, or `None`
+    /// if it has no solution.
+    fn solve(&self, puzzle: &Sudoku<N>) -> Option<Sudoku<N>>;
+}
+
+/// The original depth-first backtracker: on each empty cell it recomputes
+/// the legal digits from scratch and recurses, cloning the board per guess.
+pub struct BacktrackSolver;
+
+impl<const N: usize> Solver<N> for BacktrackSolver {
+    fn solve(&self, puzzle: &Sudoku<N>) -> Option<Sudoku<N>> {
+        Self::solve_impl(puzzle.clone())
+    }
+}
+
+impl BacktrackSolver {
+    fn solve_impl<const N: usize>(puzzle: Sudoku<N>) -> Option<Sudoku<N>> {
+        match puzzle.first_empty_index() {
+            None => {
+                if puzzle.is_valid() {
+                    Some(puzzle)
+                } else {
+                    None
+                }
+            }
+            Some(index) => Self::possible_values(&puzzle, index).into_iter().fold(
+                None,
+                |prev_result, value| {
+                    if prev_result.is_some() {
+                        return prev_result;
+                    }
+
+                    let mut puzzle = puzzle.clone();
+                    puzzle.set_field(index, value);
+                    Self::solve_impl(puzzle)
+                },
+            ),
+        }
+    }
+
+    fn possible_values<const N: usize>(puzzle: &Sudoku<N>, index: usize) -> Vec<u8> {
+        (1..=N as u8)
+            .filter(|value| {
+                !Sudoku::<N>::peers_of(index)
+                    .any(|peer| puzzle.fields[peer] == Field::Filled(*value))
+            })
+            .collect()
+    }
+}
+
+/// A row, column or square, as named in a human-readable deduction.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Unit {
+    Row(usize),
+    Column(usize),
+    Square(usize),
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unit::Row(index) => write!(f, "row {}", index + 1),
+            Unit::Column(index) => write!(f, "column {}", index + 1),
+            Unit::Square(index) => write!(f, "box {}", index + 1),
+        }
+    }
+}
+
+/// One deduction made while solving, in the order it was applied. Returned
+/// alongside the solution by `PropagationSolver::solve_with_log` to explain
+/// *why* each cell took its value rather than just producing the answer.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SolveStep {
+    /// `index`'s last remaining candidate was `value`.
+    NakedSingle { index: usize, value: u8 },
+    /// `value` fit only `index` within `unit`.
+    HiddenSingle { index: usize, value: u8, unit: Unit },
+    /// Propagation alone couldn't narrow the board further, so `value` was
+    /// guessed at `index`.
+    Guess { index: usize, value: u8 },
+    /// A previous guess at `index` led to a contradiction and was undone.
+    Backtrack { index: usize, value: u8 },
+}
+
+impl SolveStep {
+    /// Renders this step as a human-readable line, e.g. `"naked single:
+    /// r3c7 = 4"` or `"hidden single in box 5: 9 -> r4c5"`. `N` is needed to
+    /// turn a raw cell index into its 1-based row/column.
+    pub fn describe<const N: usize>(&self) -> String {
+        let cell = |index: usize| format!("r{}c{}", index / N + 1, index % N + 1);
+
+        match self {
+            SolveStep::NakedSingle { index, value } => {
+                format!("naked single: {} = {}", cell(*index), value)
+            }
+            SolveStep::HiddenSingle { index, value, unit } => {
+                format!("hidden single in {}: {} -> {}", unit, value, cell(*index))
+            }
+            SolveStep::Guess { index, value } => {
+                format!("guess: {} = {}", cell(*index), value)
+            }
+            SolveStep::Backtrack { index, value } => {
+                format!("guess: {} = {} (backtracked)", cell(*index), value)
+            }
+        }
+    }
+}
+
+/// A solver that maintains a persistent candidate bitset per cell, runs
+/// naked-single/hidden-single propagation between guesses, and backtracks on
+/// the cell with the fewest remaining candidates (minimum-remaining-value).
+pub struct PropagationSolver;
+
+impl<const N: usize> Solver<N> for PropagationSolver {
+    fn solve(&self, puzzle: &Sudoku<N>) -> Option<Sudoku<N>> {
+        Self::solve_impl(puzzle.clone())
+    }
+}
+
+impl PropagationSolver {
+    fn solve_impl<const N: usize>(mut puzzle: Sudoku<N>) -> Option<Sudoku<N>> {
+        if !puzzle.propagate() {
+            return None;
+        }
+
+        match puzzle.get_min_candidate_index() {
+            None => {
+                if puzzle.is_valid() {
+                    Some(puzzle)
+                } else {
+                    None
+                }
+            }
+            Some(index) => {
+                let candidates = puzzle.candidates[index];
+                (1..=N as u8)
+                    .filter(|value| candidates & (1 << (value - 1)) != 0)
+                    .fold(None, |prev_result, value| {
+                        if prev_result.is_some() {
+                            return prev_result;
+                        }
+
+                        let mut puzzle = puzzle.clone();
+                        puzzle.set_field(index, value);
+                        Self::solve_impl(puzzle)
+                    })
+            }
+        }
+    }
+
+    /// Like `solve`, but also returns the deduction log: every naked/hidden
+    /// single propagation, and every guess placed or later backtracked, in
+    /// the order it happened.
+    pub fn solve_with_log<const N: usize>(
+        &self,
+        puzzle: &Sudoku<N>,
+    ) -> Option<(Sudoku<N>, Vec<SolveStep>)> {
+        let mut log = Vec::new();
+        let solution = Self::solve_logged(puzzle.clone(), &mut log)?;
+        Some((solution, log))
+    }
+
+    fn solve_logged<const N: usize>(
+        mut puzzle: Sudoku<N>,
+        log: &mut Vec<SolveStep>,
+    ) -> Option<Sudoku<N>> {
+        let ok = puzzle.propagate_logged(&mut |step| log.push(step));
+        if !ok {
+            return None;
+        }
+
+        match puzzle.get_min_candidate_index() {
+            None => {
+                if puzzle.is_valid() {
+                    Some(puzzle)
+                } else {
+                    None
+                }
+            }
+            Some(index) => {
+                let candidates = puzzle.candidates[index];
+
+                for value in (1..=N as u8).filter(|value| candidates & (1 << (value - 1)) != 0) {
+                    let mut attempt = puzzle.clone();
+                    attempt.set_field(index, value);
+                    log.push(SolveStep::Guess { index, value });
+
+                    if let Some(solved) = Self::solve_logged(attempt, log) {
+                        return Some(solved);
+                    }
+
+                    log.push(SolveStep::Backtrack { index, value });
+                }
+
+                None
+            }
+        }
+    }
+
+    /// Counts up to `limit` distinct solutions of `puzzle`, stopping the
+    /// search as soon as `limit` is reached. With a `limit` of at least 2,
+    /// callers can tell "no solution" (`0`), "unique" (`1`) and "multiple"
+    /// (`>= 2`) apart without paying for a full enumeration.
+    pub fn count_solutions<const N: usize>(&self, puzzle: &Sudoku<N>, limit: usize) -> usize {
+        Self::count_impl(puzzle.clone(), limit)
+    }
+
+    fn count_impl<const N: usize>(mut puzzle: Sudoku<N>, limit: usize) -> usize {
+        if limit == 0 || !puzzle.propagate() {
+            return 0;
+        }
+
+        match puzzle.get_min_candidate_index() {
+            None => usize::from(puzzle.is_valid()),
+            Some(index) => {
+                let candidates = puzzle.candidates[index];
+                let mut found = 0;
+
+                for value in (1..=N as u8).filter(|value| candidates & (1 << (value - 1)) != 0) {
+                    let mut next = puzzle.clone();
+                    next.set_field(index, value);
+                    found += Self::count_impl(next, limit - found);
+
+                    if found >= limit {
+                        break;
+                    }
+                }
+
+                found
+            }
+        }
+    }
+}
+
+/// Every iteration count between cooling steps and stall checks is in units
+/// of individual swap attempts, not restarts.
+const ANNEALING_BATCH_SIZE: u32 = 200;
+const ANNEALING_COOLING_RATE: f64 = 0.99;
+const ANNEALING_STALL_LIMIT: u32 = 8_000;
+const ANNEALING_MAX_RESTARTS: u32 = 40;
+
+/// A stochastic solver for puzzles where candidate pruning gives little
+/// traction: it fills every box with its missing digits, then uses
+/// Metropolis simulated annealing to swap non-given cells within a box until
+/// every row and column also holds all `N` digits.
+///
+/// Unlike `BacktrackSolver`/`PropagationSolver`, a `None` result here is not
+/// proof the puzzle is unsolvable — it only means annealing didn't converge
+/// within `ANNEALING_MAX_RESTARTS` restarts of `ANNEALING_STALL_LIMIT`
+/// stalled swaps each. A genuinely solvable puzzle can still exhaust that
+/// budget; conversely, an invalid puzzle (e.g. a repeated given) always
+/// burns through the whole budget before giving up.
+pub struct AnnealingSolver;
+
+impl<const N: usize> Solver<N> for AnnealingSolver {
+    fn solve(&self, puzzle: &Sudoku<N>) -> Option<Sudoku<N>> {
+        let fixed: Vec<bool> = puzzle
+            .fields
+            .iter()
+            .map(|&field| field != Field::Empty)
+            .collect();
+        let mut rng = rand::thread_rng();
+
+        for _attempt in 0..ANNEALING_MAX_RESTARTS {
+            let mut board = Self::random_fill(puzzle, &mut rng);
+            if let Some(solved) = Self::anneal(&mut board, &fixed, &mut rng) {
+                return Some(solved);
+            }
+        }
+
+        None
+    }
+}
+
+impl AnnealingSolver {
+    /// Fills every box's empty cells with a random permutation of its
+    /// missing digits, so every box holds each of `1..=N` exactly once.
+    fn random_fill<const N: usize>(puzzle: &Sudoku<N>, rng: &mut impl Rng) -> Sudoku<N> {
+        let mut fields = puzzle.fields.clone();
+
+        for square in 0..N {
+            let indices = Sudoku::<N>::square_indices(square);
+            let present = indices
+                .iter()
+                .fold(0u16, |mask, &index| match fields[index] {
+                    Field::Filled(value) => mask | (1 << (value - 1)),
+                    Field::Empty => mask,
+                });
+
+            let mut missing: Vec<u8> = (1..=N as u8)
+                .filter(|value| present & (1 << (value - 1)) == 0)
+                .collect();
+            missing.shuffle(rng);
+            let mut missing = missing.into_iter();
+
+            for &index in indices.iter() {
+                if fields[index] == Field::Empty {
+                    fields[index] = Field::Filled(missing.next().unwrap());
+                }
+            }
+        }
+
+        Sudoku {
+            candidates: vec![0; fields.len()],
+            fields,
+        }
+    }
+
+    /// Runs Metropolis annealing on `board` until its energy reaches zero
+    /// (solved) or the search plateaus, in which case it returns `None` so
+    /// the caller can restart from a fresh random fill.
+    fn anneal<const N: usize>(
+        board: &mut Sudoku<N>,
+        fixed: &[bool],
+        rng: &mut impl Rng,
+    ) -> Option<Sudoku<N>> {
+        let mut energy = Self::energy::<N>(&board.fields);
+        if energy == 0 {
+            return Some(board.clone());
+        }
+
+        let mut temperature = Self::initial_temperature(board, fixed, rng);
+        let mut since_improvement = 0u32;
+
+        loop {
+            for _step in 0..ANNEALING_BATCH_SIZE {
+                let Some((a, b)) = Self::random_swap_candidates::<N>(fixed, rng) else {
+                    // No swap in this box; counts as a stalled attempt so a
+                    // board with no movable pair anywhere still gives up.
+                    since_improvement += 1;
+                    if since_improvement > ANNEALING_STALL_LIMIT {
+                        return None;
+                    }
+                    continue;
+                };
+
+                let delta = Self::swap_energy_delta::<N>(&mut board.fields, a, b);
+                let accept = delta <= 0 || rng.gen::<f64>() < (-delta as f64 / temperature).exp();
+
+                if accept {
+                    board.fields.swap(a, b);
+                    energy = (energy as i32 + delta) as u32;
+
+                    if energy == 0 {
+                        return Some(board.clone());
+                    }
+
+                    since_improvement = if delta < 0 { 0 } else { since_improvement + 1 };
+                } else {
+                    since_improvement += 1;
+                }
+
+                if since_improvement > ANNEALING_STALL_LIMIT {
+                    return None;
+                }
+            }
+
+            temperature *= ANNEALING_COOLING_RATE;
+        }
+    }
+
+    /// Starting temperature near the standard deviation of the energy change
+    /// caused by a sample of random swaps on the freshly filled board.
+    fn initial_temperature<const N: usize>(
+        board: &mut Sudoku<N>,
+        fixed: &[bool],
+        rng: &mut impl Rng,
+    ) -> f64 {
+        let samples: Vec<f64> = (0..ANNEALING_BATCH_SIZE)
+            .filter_map(|_| Self::random_swap_candidates::<N>(fixed, rng))
+            .map(|(a, b)| Self::swap_energy_delta::<N>(&mut board.fields, a, b) as f64)
+            .collect();
+
+        let mean = samples.iter().sum::<f64>() / samples.len().max(1) as f64;
+        let variance = samples
+            .iter()
+            .map(|delta| (delta - mean).powi(2))
+            .sum::<f64>()
+            / samples.len().max(1) as f64;
+
+        variance.sqrt().max(1.0)
+    }
+
+    /// Picks two distinct non-fixed cells from a random box to swap.
+    fn random_swap_candidates<const N: usize>(
+        fixed: &[bool],
+        rng: &mut impl Rng,
+    ) -> Option<(usize, usize)> {
+        let square = rng.gen_range(0..N);
+        let movable: Vec<usize> = Sudoku::<N>::square_indices(square)
+            .into_iter()
+            .filter(|&index| !fixed[index])
+            .collect();
+
+        if movable.len() < 2 {
+            return None;
+        }
+
+        let a = rng.gen_range(0..movable.len());
+        let b = (a + rng.gen_range(1..movable.len())) % movable.len();
+
+        Some((movable[a], movable[b]))
+    }
+
+    /// The energy change from swapping `a` and `b`, computed by only
+    /// re-scoring the (at most four) rows and columns the swap touches. The
+    /// swap is applied and then reverted, leaving `fields` unchanged.
+    fn swap_energy_delta<const N: usize>(fields: &mut [Field], a: usize, b: usize) -> i32 {
+        let units = Self::affected_units::<N>(a, b);
+
+        let before = Self::local_energy(fields, &units);
+        fields.swap(a, b);
+        let after = Self::local_energy(fields, &units);
+        fields.swap(a, b);
+
+        after as i32 - before as i32
+    }
+
+    fn affected_units<const N: usize>(a: usize, b: usize) -> Vec<Vec<usize>> {
+        let (row_a, col_a) = (a / N, a % N);
+        let (row_b, col_b) = (b / N, b % N);
+
+        let mut units = vec![
+            Sudoku::<N>::row_indices(row_a),
+            Sudoku::<N>::col_indices(col_a),
+        ];
+        if row_b != row_a {
+            units.push(Sudoku::<N>::row_indices(row_b));
+        }
+        if col_b != col_a {
+            units.push(Sudoku::<N>::col_indices(col_b));
+        }
+
+        units
+    }
+
+    fn local_energy(fields: &[Field], units: &[Vec<usize>]) -> u32 {
+        units
+            .iter()
+            .map(|unit| Self::missing_count(fields, unit))
+            .sum()
+    }
+
+    /// Total energy of a fully-filled board: the number of missing distinct
+    /// digits summed over every row and column (zero means solved).
+    fn energy<const N: usize>(fields: &[Field]) -> u32 {
+        (0..N)
+            .map(Sudoku::<N>::row_indices)
+            .chain((0..N).map(Sudoku::<N>::col_indices))
+            .map(|unit| Self::missing_count(fields, &unit))
+            .sum()
+    }
+
+    fn missing_count(fields: &[Field], indices: &[usize]) -> u32 {
+        let present = indices
+            .iter()
+            .fold(0u16, |mask, &index| match fields[index] {
+                Field::Filled(value) => mask | (1 << (value - 1)),
+                Field::Empty => mask,
+            });
+
+        indices.len() as u32 - present.count_ones()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(str_puzzle: &str) -> Sudoku<9> {
+        Sudoku::<9>::read_from(str_puzzle.as_bytes()).unwrap()
+    }
+
+    const EASY_PUZZLE: &str = "* 8 6 * 4 1 * 3 9\n* 4 * * * 7 8 * *\n* * 9 * * 6 2 4 *\n7 3 * * * 4 6 * *\n1 * * 2 * * * 9 5\n* * * 6 5 * * 7 4\n* * 2 * 6 9 5 * 3\n8 * * 3 1 * * * 2\n6 5 3 * * * 9 * *\n";
+
+    const EASY_SOLUTION: &str = "2 8 6 5 4 1 7 3 9\n3 4 1 9 2 7 8 5 6\n5 7 9 8 3 6 2 4 1\n7 3 5 1 9 4 6 2 8\n1 6 4 2 7 8 3 9 5\n9 2 8 6 5 3 1 7 4\n4 1 2 7 6 9 5 8 3\n8 9 7 3 1 5 4 6 2\n6 5 3 4 8 2 9 1 7\n";
+
+    #[test]
+    fn backtrack_solver_matches_propagation_solver() {
+        let puzzle = parse(EASY_PUZZLE);
+        let answer = parse(EASY_SOLUTION);
+
+        assert_eq!(BacktrackSolver.solve(&puzzle).unwrap(), answer);
+        assert_eq!(PropagationSolver.solve(&puzzle).unwrap(), answer);
+    }
+
+    #[test]
+    fn annealing_solver_finds_the_solution() {
+        let puzzle = parse(EASY_PUZZLE);
+        let answer = parse(EASY_SOLUTION);
+
+        assert_eq!(AnnealingSolver.solve(&puzzle).unwrap(), answer);
+    }
+
+    #[test]
+    fn count_solutions_reports_uniqueness() {
+        let puzzle = parse(EASY_PUZZLE);
+
+        assert_eq!(PropagationSolver.count_solutions(&puzzle, 2), 1);
+    }
+
+    #[test]
+    fn count_solutions_short_circuits_at_the_limit() {
+        let empty = parse(&"* * * * * * * * *\n".repeat(9));
+
+        assert_eq!(PropagationSolver.count_solutions(&empty, 2), 2);
+    }
+
+    #[test]
+    fn solve_with_log_matches_solve_and_logs_something() {
+        let puzzle = parse(EASY_PUZZLE);
+        let answer = parse(EASY_SOLUTION);
+
+        let (solution, log) = PropagationSolver.solve_with_log(&puzzle).unwrap();
+
+        assert_eq!(solution, answer);
+        assert!(!log.is_empty());
+        assert!(log
+            .iter()
+            .any(|step| matches!(step, SolveStep::NakedSingle { .. })));
+    }
+
+    #[test]
+    fn solve_with_log_records_guesses() {
+        // A puzzle hard enough that pure propagation stalls and the solver
+        // has to guess: eight fully empty rows force naked/hidden singles to
+        // run dry well before the board fills, so at least one guess must
+        // be attempted.
+        let hard_puzzle = "* 8 6 * 4 1 * 3 9\n".to_owned()
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n"
+            + "* * * * * * * * *\n";
+        let puzzle = parse(&hard_puzzle);
+
+        let (_solution, log) = PropagationSolver.solve_with_log(&puzzle).unwrap();
+
+        assert!(log
+            .iter()
+            .any(|step| matches!(step, SolveStep::Guess { .. })));
+    }
+
+    #[test]
+    fn solve_step_describe_formats_each_variant() {
+        assert_eq!(
+            SolveStep::NakedSingle { index: 24, value: 4 }.describe::<9>(),
+            "naked single: r3c7 = 4"
+        );
+        assert_eq!(
+            SolveStep::HiddenSingle {
+                index: 31,
+                value: 9,
+                unit: Unit::Square(4),
+            }
+            .describe::<9>(),
+            "hidden single in box 5: 9 -> r4c5"
+        );
+        assert_eq!(
+            SolveStep::Guess { index: 0, value: 2 }.describe::<9>(),
+            "guess: r1c1 = 2"
+        );
+        assert_eq!(
+            SolveStep::Backtrack { index: 0, value: 2 }.describe::<9>(),
+            "guess: r1c1 = 2 (backtracked)"
+        );
+    }
+}